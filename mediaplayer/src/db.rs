@@ -0,0 +1,30 @@
+//! Simpel JSON-baseret database, så afspilningslister overlever mellem
+//! sessioner.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{MusicError, Playlists};
+
+pub struct JsonDatabase;
+
+impl JsonDatabase {
+    /// Indlæser playlisterne fra `path`. Findes filen ikke endnu, returneres
+    /// en tom database i stedet for en fejl, så første kørsel virker uden
+    /// en eksisterende fil.
+    pub fn load(path: &Path) -> Result<Playlists, MusicError> {
+        if !path.exists() {
+            return Ok(Playlists::new());
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| MusicError::DatabaseError(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| MusicError::DatabaseError(e.to_string()))
+    }
+
+    /// Gemmer playlisterne til `path` i et menneskeligt læsbart JSON-format.
+    pub fn save(playlists: &Playlists, path: &Path) -> Result<(), MusicError> {
+        let content = serde_json::to_string_pretty(playlists)
+            .map_err(|e| MusicError::DatabaseError(e.to_string()))?;
+        fs::write(path, content).map_err(|e| MusicError::DatabaseError(e.to_string()))
+    }
+}
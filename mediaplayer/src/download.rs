@@ -0,0 +1,81 @@
+//! Henter sange fra en URL ned i det administrerede mediebibliotek.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use futures_util::StreamExt;
+
+use crate::model::Song;
+use crate::{MusicError, Playlists};
+
+const MEDIA_DIR: &str = "media";
+
+pub struct Downloader;
+
+impl Downloader {
+    /// Henter `url` ned lokalt og indsætter den resulterende sang i
+    /// `playlist` (oprettes hvis den ikke findes i forvejen).
+    pub async fn add(
+        url: &str,
+        title: &str,
+        playlist: &str,
+        playlists: &mut Playlists,
+    ) -> Result<Song, MusicError> {
+        fs::create_dir_all(MEDIA_DIR).map_err(|e| MusicError::IoError(e.to_string()))?;
+
+        let response = reqwest::get(url).await.map_err(|_| MusicError::Offline)?;
+        let total = response.content_length();
+        let path = destination(url, title);
+
+        let mut file = fs::File::create(&path).map_err(|e| MusicError::DownloadFailed(e.to_string()))?;
+        let mut stream = response.bytes_stream();
+        let mut downloaded: u64 = 0;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| MusicError::DownloadFailed(e.to_string()))?;
+            file.write_all(&chunk)
+                .map_err(|e| MusicError::DownloadFailed(e.to_string()))?;
+            downloaded += chunk.len() as u64;
+            print_progress(downloaded, total);
+        }
+        println!();
+
+        let song = Song::new(title, path);
+        let songs = playlists.entry(playlist.to_string()).or_default();
+        *songs = crate::model::merge(std::mem::take(songs), vec![song.clone()]);
+        Ok(song)
+    }
+}
+
+fn destination(url: &str, title: &str) -> PathBuf {
+    let ext = extension_from_url(url);
+    let filename: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    [MEDIA_DIR, &format!("{}.{}", filename, ext)].iter().collect()
+}
+
+/// Udleder filendelsen fra `url`'s sti alene, uden forespørgselsstreng eller
+/// fragment — ellers bliver f.eks. `…/sang.mp3?token=x` til endelsen
+/// `mp3?token=x` i stedet for `mp3`.
+fn extension_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let decoded = urlencoding::decode(path)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    Path::new(&decoded)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp3")
+        .to_string()
+}
+
+fn print_progress(downloaded: u64, total: Option<u64>) {
+    match total {
+        Some(total) if total > 0 => print!("\r  ⬇  {:>3} %", downloaded * 100 / total),
+        _ => print!("\r  ⬇  {} bytes", downloaded),
+    }
+    let _ = std::io::stdout().flush();
+}
@@ -1,5 +1,25 @@
 use std::collections::HashMap;
+use std::fs::File;
 use std::io::{self, Write};
+use std::path::Path;
+
+mod db;
+mod download;
+mod model;
+mod mpris;
+mod musicbrainz;
+mod player;
+mod playlist_format;
+mod search;
+
+use db::JsonDatabase;
+use download::Downloader;
+use model::Song;
+use mpris::MprisController;
+use musicbrainz::MusicBrainzDaemon;
+use player::PlayerHandle;
+
+const DB_PATH: &str = "playlists.json";
 
 // ── Fejltyper ────────────────────────────────────────────────────────────────
 
@@ -11,7 +31,14 @@ enum MusicError {
     SongNotFound(String),
     EmptyPlaylist(String),
     Offline,
-    InvalidUser,
+    UnsupportedFormat(String),
+    DecodeError(String),
+    IoError(String),
+    DatabaseError(String),
+    LookupFailed(String),
+    InvalidPattern(String),
+    DownloadFailed(String),
+    MprisUnavailable(String),
 }
 
 impl std::fmt::Display for MusicError {
@@ -23,12 +50,19 @@ impl std::fmt::Display for MusicError {
             MusicError::SongNotFound(s)          => write!(f, "Sangen '{}' findes ikke.", s),
             MusicError::EmptyPlaylist(n)         => write!(f, "Playlist '{}' er tom.", n),
             MusicError::Offline                  => write!(f, "Ingen internetforbindelse – prøv igen."),
-            MusicError::InvalidUser              => write!(f, "Ugyldigt brugernavn."),
+            MusicError::UnsupportedFormat(ext)    => write!(f, "Filformatet '{}' understøttes ikke.", ext),
+            MusicError::DecodeError(msg)          => write!(f, "Kunne ikke afspille filen: {}", msg),
+            MusicError::IoError(msg)              => write!(f, "I/O-fejl: {}", msg),
+            MusicError::DatabaseError(msg)        => write!(f, "Databasefejl: {}", msg),
+            MusicError::LookupFailed(msg)         => write!(f, "MusicBrainz-opslag fejlede: {}", msg),
+            MusicError::InvalidPattern(msg)       => write!(f, "Ugyldigt regulært udtryk: {}", msg),
+            MusicError::DownloadFailed(msg)       => write!(f, "Download mislykkedes: {}", msg),
+            MusicError::MprisUnavailable(msg)     => write!(f, "MPRIS kunne ikke startes: {}", msg),
         }
     }
 }
 
-type Playlists = HashMap<String, Vec<String>>;
+type Playlists = HashMap<String, Vec<Song>>;
 
 // ── Backendlogik ─────────────────────────────────────────────────────────────
 
@@ -40,19 +74,22 @@ fn create_playlist(playlists: &mut Playlists, name: &str) -> Result<(), MusicErr
     Ok(())
 }
 
-fn add_song(playlists: &mut Playlists, playlist: &str, song: &str) -> Result<(), MusicError> {
+fn add_song(playlists: &mut Playlists, playlist: &str, song: Song) -> Result<(), MusicError> {
     let songs = playlists
         .get_mut(playlist)
         .ok_or_else(|| MusicError::PlaylistNotFound(playlist.to_string()))?;
 
-    if songs.contains(&song.to_string()) {
-        return Err(MusicError::SongAlreadyInPlaylist(song.to_string()));
+    let before = songs.len();
+    let merged = model::merge(std::mem::take(songs), vec![song.clone()]);
+    let after = merged.len();
+    *songs = merged;
+    if after == before {
+        return Err(MusicError::SongAlreadyInPlaylist(song.title));
     }
-    songs.push(song.to_string());
     Ok(())
 }
 
-fn play_song(playlists: &Playlists, playlist: &str, song: &str, online: bool) -> Result<String, MusicError> {
+fn find_song<'a>(playlists: &'a Playlists, playlist: &str, title: &str) -> Result<&'a Song, MusicError> {
     let songs = playlists
         .get(playlist)
         .ok_or_else(|| MusicError::PlaylistNotFound(playlist.to_string()))?;
@@ -63,14 +100,8 @@ fn play_song(playlists: &Playlists, playlist: &str, song: &str, online: bool) ->
 
     songs
         .iter()
-        .find(|s| s.as_str() == song)
-        .ok_or_else(|| MusicError::SongNotFound(song.to_string()))?;
-
-    if !online {
-        return Err(MusicError::Offline);
-    }
-
-    Ok(format!("♪  Afspiller nu: '{}'  ♪", song))
+        .find(|s| s.title == title)
+        .ok_or_else(|| MusicError::SongNotFound(title.to_string()))
 }
 
 // ── TUI-hjælpere ─────────────────────────────────────────────────────────────
@@ -93,6 +124,11 @@ fn print_menu() {
     println!("│  [2]  Tilføj sang til liste           │");
     println!("│  [3]  Afspil sang                     │");
     println!("│  [4]  Vis alle lister og sange        │");
+    println!("│  [5]  Importér liste                  │");
+    println!("│  [6]  Eksportér liste                 │");
+    println!("│  [7]  Berig metadata                  │");
+    println!("│  [8]  Søg                             │");
+    println!("│  [9]  Hent sang fra URL               │");
     println!("│  [0]  Afslut                          │");
     println!("└──────────────────────────────────────┘");
     print!("  Vælg: ");
@@ -127,14 +163,26 @@ fn print_playlists(playlists: &Playlists) {
         println!("  📁  {}", name);
         if songs.is_empty() {
             println!("       (ingen sange)");
-        } else {
-            for (i, song) in songs.iter().enumerate() {
-                println!("       {}. {}", i + 1, song);
+            continue;
+        }
+
+        for album in model::group_by_album(songs) {
+            println!("       💿  {}", album_header(&album.id));
+            for (i, song) in album.tracks.iter().enumerate() {
+                println!("            {}. {}", i + 1, song.title);
             }
         }
     }
 }
 
+fn album_header(id: &model::AlbumId) -> String {
+    let title = if id.title.is_empty() { "(ukendt album)" } else { &id.title };
+    match id.year {
+        Some(year) => format!("{} ({})", title, year),
+        None => title.to_string(),
+    }
+}
+
 fn pause() {
     println!();
     prompt("Tryk Enter for at fortsætte...");
@@ -152,7 +200,12 @@ fn handle_create(playlists: &mut Playlists) {
         println!("\n  ⚠️   Navn må ikke være tomt.");
     } else {
         match create_playlist(playlists, &name) {
-            Ok(_)  => print_ok(&format!("Playlist '{}' oprettet!", name)),
+            Ok(_)  => {
+                print_ok(&format!("Playlist '{}' oprettet!", name));
+                if let Err(e) = JsonDatabase::save(playlists, Path::new(DB_PATH)) {
+                    print_err(&e);
+                }
+            }
             Err(e) => print_err(&e),
         }
     }
@@ -168,20 +221,27 @@ fn handle_add_song(playlists: &mut Playlists) {
     println!();
 
     let playlist = prompt("Navn på afspilningsliste:");
-    let song     = prompt("Sangnavn:");
+    let title    = prompt("Sangtitel:");
+    let path     = prompt("Sti til fil:");
 
-    if playlist.is_empty() || song.is_empty() {
+    if playlist.is_empty() || title.is_empty() || path.is_empty() {
         println!("\n  ⚠️   Ingen felter må være tomme.");
     } else {
-        match add_song(playlists, &playlist, &song) {
-            Ok(_)  => print_ok(&format!("'{}' tilføjet til '{}'!", song, playlist)),
+        let song = Song::new(&title, &path);
+        match add_song(playlists, &playlist, song) {
+            Ok(_)  => {
+                print_ok(&format!("'{}' tilføjet til '{}'!", title, playlist));
+                if let Err(e) = JsonDatabase::save(playlists, Path::new(DB_PATH)) {
+                    print_err(&e);
+                }
+            }
             Err(e) => print_err(&e),
         }
     }
     pause();
 }
 
-fn handle_play(playlists: &Playlists) {
+fn handle_play(playlists: &Playlists, player: &PlayerHandle, mpris: &MprisController) {
     clear_screen();
     print_header();
     println!("  ── Afspil sang ──\n");
@@ -190,29 +250,51 @@ fn handle_play(playlists: &Playlists) {
     println!();
 
     let playlist = prompt("Navn på afspilningsliste:");
-    let song     = prompt("Sangnavn:");
-    let online_s = prompt("Er du online? (j/n):");
-    let online   = online_s.to_lowercase() == "j";
+    let title    = prompt("Sangtitel:");
 
-    if playlist.is_empty() || song.is_empty() {
+    if playlist.is_empty() || title.is_empty() {
         println!("\n  ⚠️   Ingen felter må være tomme.");
-    } else {
-        match play_song(playlists, &playlist, &song, online) {
-            Ok(msg) => print_ok(&msg),
-            Err(e)  => {
-                print_err(&e);
-                // Giver brugeren mulighed for at prøve igen ved offline-fejl
-                if let MusicError::Offline = e {
-                    let retry = prompt("Prøv igen? (j/n):");
-                    if retry.to_lowercase() == "j" {
-                        match play_song(playlists, &playlist, &song, true) {
-                            Ok(msg) => print_ok(&msg),
-                            Err(e2) => print_err(&e2),
-                        }
-                    }
-                }
+        pause();
+        return;
+    }
+
+    match find_song(playlists, &playlist, &title) {
+        Ok(song) => match player.play(song.clone()) {
+            Ok(())  => print_ok(&format!("Afspiller '{}'.", title)),
+            Err(e)  => print_err(&e),
+        },
+        Err(e) => print_err(&e),
+    }
+    mpris.sync(&player.status(), &playlist);
+
+    playback_controls(player, mpris, &playlist);
+}
+
+/// Lille undermenu der holder afspilningen kørende, mens brugeren kan
+/// pause/genoptage/stoppe uden at skulle igennem hovedmenuen igen.
+///
+/// `player` er et billigt klonbart håndtag til afspilleren på sin egen
+/// baggrundstråd, så en MPRIS-kommando fra skrivebordet kan slå igennem med
+/// det samme i stedet for at vente på, at `prompt` herunder returnerer.
+fn playback_controls(player: &PlayerHandle, mpris: &MprisController, playlist: &str) {
+    loop {
+        println!("\n  {}", player.status());
+        let choice = prompt("[p] Pause  [r] Genoptag  [s] Stop  [Enter] Tilbage:");
+        match choice.to_lowercase().as_str() {
+            "p" => player.pause(),
+            "r" => player.resume(),
+            "s" => {
+                player.stop();
+                mpris.sync(&player.status(), playlist);
+                break;
+            }
+            "" => break,
+            _ => {
+                println!("\n  ⚠️   Ugyldigt valg.");
+                continue;
             }
         }
+        mpris.sync(&player.status(), playlist);
     }
     pause();
 }
@@ -225,14 +307,241 @@ fn handle_list(playlists: &Playlists) {
     pause();
 }
 
+fn handle_import(playlists: &mut Playlists) {
+    clear_screen();
+    print_header();
+    println!("  ── Importér liste ──\n");
+
+    let path_s  = prompt("Sti til fil (.m3u/.pls/.xspf):");
+    let playlist = prompt("Navn på afspilningsliste:");
+
+    if path_s.is_empty() || playlist.is_empty() {
+        println!("\n  ⚠️   Ingen felter må være tomme.");
+        pause();
+        return;
+    }
+
+    match import_playlist(&path_s, &playlist, playlists) {
+        Ok(n)  => {
+            print_ok(&format!("{} sang(e) importeret til '{}'.", n, playlist));
+            if let Err(e) = JsonDatabase::save(playlists, Path::new(DB_PATH)) {
+                print_err(&e);
+            }
+        }
+        Err(e) => print_err(&e),
+    }
+    pause();
+}
+
+fn handle_export(playlists: &Playlists) {
+    clear_screen();
+    print_header();
+    println!("  ── Eksportér liste ──\n");
+
+    print_playlists(playlists);
+    println!();
+
+    let playlist = prompt("Navn på afspilningsliste:");
+    let path_s   = prompt("Sti til fil (.m3u/.pls/.xspf):");
+
+    if playlist.is_empty() || path_s.is_empty() {
+        println!("\n  ⚠️   Ingen felter må være tomme.");
+        pause();
+        return;
+    }
+
+    match export_playlist(playlists, &playlist, &path_s) {
+        Ok(()) => print_ok(&format!("Playlist '{}' eksporteret til '{}'.", playlist, path_s)),
+        Err(e) => print_err(&e),
+    }
+    pause();
+}
+
+fn handle_enrich(playlists: &Playlists, daemon: &MusicBrainzDaemon) {
+    clear_screen();
+    print_header();
+    println!("  ── Berig metadata ──\n");
+
+    print_playlists(playlists);
+    println!();
+
+    let playlist = prompt("Navn på afspilningsliste:");
+    let title    = prompt("Sangtitel:");
+
+    if playlist.is_empty() || title.is_empty() {
+        println!("\n  ⚠️   Ingen felter må være tomme.");
+    } else {
+        match find_song(playlists, &playlist, &title) {
+            Ok(song) => {
+                daemon.request(playlist.clone(), song.clone());
+                print_ok("Forespørgsel sendt til MusicBrainz — du kan fortsætte med det samme.");
+            }
+            Err(e) => print_err(&e),
+        }
+    }
+    pause();
+}
+
+/// Indarbejder alle svar dæmonen har sendt tilbage siden sidst, uden at
+/// blokere hovedløkken.
+fn apply_enrichment_results(playlists: &mut Playlists, daemon: &MusicBrainzDaemon) {
+    for result in daemon.poll() {
+        match result.enriched {
+            Ok(enriched) => {
+                if let Some(songs) = playlists.get_mut(&result.playlist) {
+                    *songs = model::merge(std::mem::take(songs), vec![enriched]);
+                    if let Err(e) = JsonDatabase::save(playlists, Path::new(DB_PATH)) {
+                        print_err(&e);
+                    }
+                    println!("\n  ✅  Metadata opdateret for '{}' i '{}'.", result.title, result.playlist);
+                }
+            }
+            Err(e) => print_err(&e),
+        }
+    }
+}
+
+fn handle_search(playlists: &Playlists, player: &PlayerHandle, mpris: &MprisController) {
+    clear_screen();
+    print_header();
+    println!("  ── Søg ──\n");
+
+    let query = prompt("Søgeord (eller /regex/):");
+    if query.is_empty() {
+        println!("\n  ⚠️   Søgeordet må ikke være tomt.");
+        pause();
+        return;
+    }
+
+    match search::search(playlists, &query) {
+        Ok(hits) if hits.is_empty() => {
+            println!("\n  (ingen match)");
+            pause();
+        }
+        Ok(hits) => {
+            println!();
+            for (i, (playlist, title)) in hits.iter().enumerate() {
+                println!("  {}. {} — {}", i + 1, title, playlist);
+            }
+
+            let choice = prompt("\n  Afspil nummer (eller Enter for at springe over):");
+            match choice.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| hits.get(i)) {
+                Some(song) => {
+                    let (playlist, title) = song;
+                    match find_song(playlists, playlist, title) {
+                        Ok(found) => match player.play(found.clone()) {
+                            Ok(())  => print_ok(&format!("Afspiller '{}'.", title)),
+                            Err(e)  => print_err(&e),
+                        },
+                        Err(e) => print_err(&e),
+                    }
+                    mpris.sync(&player.status(), playlist);
+                    playback_controls(player, mpris, playlist);
+                }
+                None => pause(),
+            }
+        }
+        Err(e) => {
+            print_err(&e);
+            pause();
+        }
+    }
+}
+
+fn handle_download(playlists: &mut Playlists) {
+    clear_screen();
+    print_header();
+    println!("  ── Hent sang fra URL ──\n");
+
+    let url      = prompt("URL:");
+    let title    = prompt("Titel:");
+    let playlist = prompt("Navn på afspilningsliste:");
+
+    if url.is_empty() || title.is_empty() || playlist.is_empty() {
+        println!("\n  ⚠️   Ingen felter må være tomme.");
+        pause();
+        return;
+    }
+
+    println!();
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => {
+            print_err(&MusicError::DownloadFailed(e.to_string()));
+            pause();
+            return;
+        }
+    };
+
+    match runtime.block_on(Downloader::add(&url, &title, &playlist, playlists)) {
+        Ok(song) => {
+            print_ok(&format!("'{}' hentet og tilføjet til '{}'.", song.title, playlist));
+            if let Err(e) = JsonDatabase::save(playlists, Path::new(DB_PATH)) {
+                print_err(&e);
+            }
+        }
+        Err(e) => print_err(&e),
+    }
+    pause();
+}
+
+fn import_playlist(path_s: &str, playlist: &str, playlists: &mut Playlists) -> Result<usize, MusicError> {
+    let path = Path::new(path_s);
+    let format = playlist_format::format_for_path(path)?;
+    let mut file = File::open(path).map_err(|e| MusicError::IoError(e.to_string()))?;
+    let mut imported = format.parse(&mut file)?;
+    imported.sort();
+
+    let songs = playlists.entry(playlist.to_string()).or_default();
+    let before = songs.len();
+    *songs = model::merge(std::mem::take(songs), imported);
+    Ok(songs.len() - before)
+}
+
+fn export_playlist(playlists: &Playlists, playlist: &str, path_s: &str) -> Result<(), MusicError> {
+    let songs = playlists
+        .get(playlist)
+        .ok_or_else(|| MusicError::PlaylistNotFound(playlist.to_string()))?;
+
+    let path = Path::new(path_s);
+    let format = playlist_format::format_for_path(path)?;
+    let mut file = File::create(path).map_err(|e| MusicError::IoError(e.to_string()))?;
+    format.write(songs, &mut file)
+}
+
 // ── Main loop ────────────────────────────────────────────────────────────────
 
 fn main() {
-    let mut playlists: Playlists = HashMap::new();
+    let mut playlists: Playlists = match JsonDatabase::load(Path::new(DB_PATH)) {
+        Ok(p)  => p,
+        Err(e) => {
+            print_err(&e);
+            HashMap::new()
+        }
+    };
+
+    let player = match PlayerHandle::spawn() {
+        Ok(p) => p,
+        Err(e) => {
+            print_err(&e);
+            return;
+        }
+    };
+
+    let mpris = match MprisController::spawn(player.clone()) {
+        Ok(m) => m,
+        Err(e) => {
+            print_err(&e);
+            return;
+        }
+    };
+
+    let daemon = MusicBrainzDaemon::spawn();
 
     loop {
         clear_screen();
         print_header();
+        apply_enrichment_results(&mut playlists, &daemon);
         print_menu();
 
         let mut choice = String::new();
@@ -241,9 +550,17 @@ fn main() {
         match choice.trim() {
             "1" => handle_create(&mut playlists),
             "2" => handle_add_song(&mut playlists),
-            "3" => handle_play(&playlists),
+            "3" => handle_play(&playlists, &player, &mpris),
             "4" => handle_list(&playlists),
+            "5" => handle_import(&mut playlists),
+            "6" => handle_export(&playlists),
+            "7" => handle_enrich(&playlists, &daemon),
+            "8" => handle_search(&playlists, &player, &mpris),
+            "9" => handle_download(&mut playlists),
             "0" => {
+                if let Err(e) = JsonDatabase::save(&playlists, Path::new(DB_PATH)) {
+                    print_err(&e);
+                }
                 clear_screen();
                 println!("  Farvel! 👋");
                 break;
@@ -254,4 +571,37 @@ fn main() {
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_song_rejects_duplicate_without_emptying_playlist() {
+        let mut playlists = Playlists::new();
+        playlists.insert(
+            "Favoritter".to_string(),
+            vec![
+                Song::new("Alpha", "a.mp3"),
+                Song::new("Beta", "b.mp3"),
+                Song::new("Gamma", "g.mp3"),
+            ],
+        );
+
+        let result = add_song(&mut playlists, "Favoritter", Song::new("Beta", "b.mp3"));
+
+        assert!(matches!(result, Err(MusicError::SongAlreadyInPlaylist(title)) if title == "Beta"));
+        assert_eq!(playlists["Favoritter"].len(), 3);
+    }
+
+    #[test]
+    fn add_song_inserts_new_song() {
+        let mut playlists = Playlists::new();
+        playlists.insert("Favoritter".to_string(), vec![Song::new("Alpha", "a.mp3")]);
+
+        add_song(&mut playlists, "Favoritter", Song::new("Beta", "b.mp3")).unwrap();
+
+        assert_eq!(playlists["Favoritter"].len(), 2);
+    }
 }
\ No newline at end of file
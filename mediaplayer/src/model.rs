@@ -0,0 +1,168 @@
+//! Det strukturerede sang-/albumbillede af en afspilningsliste.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Song {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub year: Option<u32>,
+    pub duration: Option<u32>,
+    pub path: PathBuf,
+}
+
+impl Song {
+    pub fn new(title: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            title: title.into(),
+            artist: String::new(),
+            album: String::new(),
+            year: None,
+            duration: None,
+            path: path.into(),
+        }
+    }
+
+    /// Gætter en titel ud fra filnavnet, så importerede stier får en titel.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let title = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("ukendt sang")
+            .to_string();
+        Self::new(title, path)
+    }
+
+    /// Udfylder tomme felter fra `other` — bruges af [`merge`] til at slå to
+    /// kendskab til samme sang sammen uden at miste allerede kendt metadata.
+    fn merge_fields(&mut self, other: &Song) {
+        if self.artist.is_empty() {
+            self.artist = other.artist.clone();
+        }
+        if self.album.is_empty() {
+            self.album = other.album.clone();
+        }
+        if self.year.is_none() {
+            self.year = other.year;
+        }
+        if self.duration.is_none() {
+            self.duration = other.duration;
+        }
+    }
+}
+
+impl PartialOrd for Song {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Song {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.title.cmp(&other.title)
+    }
+}
+
+/// Slår to *sorterede* spor-lister sammen uden at duplikere sange.
+///
+/// Går gennem begge lister med to pegepinde: når de forreste elementer er
+/// ens, slås deres felter sammen (ikke-tomme felter foretrækkes) og begge
+/// pegepinde rykker videre; ellers emitteres det mindste element, og kun dets
+/// pegepind rykker videre. Idempotent: at merge en liste med sig selv giver
+/// listen selv igen.
+pub fn merge(a: Vec<Song>, b: Vec<Song>) -> Vec<Song> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => match x.cmp(y) {
+                Ordering::Equal => {
+                    let mut x = a.next().unwrap();
+                    let y = b.next().unwrap();
+                    x.merge_fields(&y);
+                    merged.push(x);
+                }
+                Ordering::Less => merged.push(a.next().unwrap()),
+                Ordering::Greater => merged.push(b.next().unwrap()),
+            },
+            (Some(_), None) => merged.push(a.next().unwrap()),
+            (None, Some(_)) => merged.push(b.next().unwrap()),
+            (None, None) => break,
+        }
+    }
+
+    merged
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AlbumId {
+    pub year: Option<u32>,
+    pub title: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Album {
+    pub id: AlbumId,
+    pub tracks: Vec<Song>,
+}
+
+/// Grupperer en flad spor-liste i albums, sorteret efter `(year, title)`.
+pub fn group_by_album(songs: &[Song]) -> Vec<Album> {
+    let mut groups: BTreeMap<AlbumId, Vec<Song>> = BTreeMap::new();
+    for song in songs {
+        let id = AlbumId {
+            year: song.year,
+            title: song.album.clone(),
+        };
+        groups.entry(id).or_default().push(song.clone());
+    }
+    groups
+        .into_iter()
+        .map(|(id, tracks)| Album { id, tracks })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_is_idempotent() {
+        let songs = vec![Song::new("Alpha", "a.mp3"), Song::new("Beta", "b.mp3")];
+        let merged = merge(songs.clone(), songs.clone());
+        assert_eq!(merged, songs);
+    }
+
+    #[test]
+    fn merge_dedupes_by_title() {
+        let a = vec![
+            Song::new("Alpha", "a.mp3"),
+            Song::new("Beta", "b.mp3"),
+            Song::new("Gamma", "g.mp3"),
+        ];
+        let b = vec![Song::new("Beta", "b.mp3")];
+
+        let merged = merge(a, b);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1].title, "Beta");
+    }
+
+    #[test]
+    fn merge_fills_in_missing_fields() {
+        let mut known = Song::new("Alpha", "a.mp3");
+        known.artist = "Kendt Kunstner".to_string();
+
+        let enriched = merge(vec![known], vec![Song::new("Alpha", "a.mp3")]);
+
+        assert_eq!(enriched[0].artist, "Kendt Kunstner");
+    }
+}
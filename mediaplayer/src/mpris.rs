@@ -0,0 +1,128 @@
+//! MPRIS-integration, så OS-medietaster og skrivebordspaneler kan styre
+//! afspilleren uden at brugeren rører terminalen.
+//!
+//! Bygger på `mpris-player` frem for `mpris`: `mpris` er et klientbibliotek
+//! til at *styre* andre afspilleres D-Bus-service, mens vi skal *udbyde*
+//! vores egen MPRIS-service på bussen. `mpris-player` eksponerer netop den
+//! server-side API (registrering af service, metadata, afspilningsstatus).
+
+use std::sync::mpsc;
+use std::thread;
+
+use dbus::{BusType, Connection};
+use mpris_player::{Metadata, MprisPlayer, PlaybackStatus};
+
+use crate::model::Song;
+use crate::player::{MusicPlayerStatus, PlayerHandle};
+use crate::MusicError;
+
+/// `MprisPlayer` er bygget på `Rc`/`RefCell`, så den kan hverken sendes til
+/// eller deles med andre tråde. Vi kan derfor ikke holde den i
+/// `MprisController`; i stedet ejer MPRIS-tråden den fuldt ud, og
+/// `MprisController` sender blot statusopdateringer ind via denne kommando.
+enum Command {
+    Sync(MusicPlayerStatus, String),
+}
+
+/// Spejler [`MusicPlayerStatus`] ind i MPRIS og oversætter indkommende
+/// MPRIS-kommandoer til de samme handlinger som TUI-menuen udløser.
+pub struct MprisController {
+    tx: glib::Sender<Command>,
+}
+
+impl MprisController {
+    pub fn spawn(player: PlayerHandle) -> Result<Self, MusicError> {
+        // `MprisPlayer::new` kalder internt `Connection::get_private(..).unwrap()`,
+        // så uden en sessionsbus ville den panic'e og tage hele appen med sig.
+        // Vi tjekker derfor busset selv først, så fraværet bliver en almindelig fejl.
+        Connection::get_private(BusType::Session)
+            .map_err(|e| MusicError::MprisUnavailable(e.to_string()))?;
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let handle = MprisPlayer::new(
+                "musikmanager".to_string(),
+                "Musik Manager TUI".to_string(),
+                "musikmanager".to_string(),
+            );
+            handle.set_can_play(true);
+            handle.set_can_pause(true);
+            handle.set_can_go_next(false);
+            handle.set_can_go_previous(false);
+
+            {
+                let player = player.clone();
+                handle.connect_play_pause(move || match player.status() {
+                    MusicPlayerStatus::NowPlaying(_) => player.pause(),
+                    MusicPlayerStatus::Paused(_) => player.resume(),
+                    MusicPlayerStatus::Stopped(_) => {}
+                });
+            }
+            {
+                let player = player.clone();
+                handle.connect_play(move || player.resume());
+            }
+            {
+                let player = player.clone();
+                handle.connect_pause(move || player.pause());
+            }
+            {
+                let player = player.clone();
+                handle.connect_stop(move || player.stop());
+            }
+
+            let (tx, rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+            rx.attach(None, move |command| {
+                apply(&handle, command);
+                glib::Continue(true)
+            });
+
+            if ready_tx.send(tx).is_err() {
+                return;
+            }
+
+            // `mpris-player` polier indkommende D-Bus-kald fra en glib-timeout,
+            // så denne tråd skal køre en glib-hovedløkke så længe appen lever —
+            // ellers bliver play/pause/stop fra skrivebordet registreret, men
+            // aldrig afsendt.
+            glib::MainLoop::new(None, false).run();
+        });
+
+        let tx = ready_rx
+            .recv()
+            .map_err(|_| MusicError::MprisUnavailable("MPRIS-tråden stoppede under opstart.".to_string()))?;
+
+        Ok(Self { tx })
+    }
+
+    /// Opdaterer MPRIS-metadata og afspilningsstatus til at matche `status`.
+    pub fn sync(&self, status: &MusicPlayerStatus, playlist: &str) {
+        let _ = self.tx.send(Command::Sync(status.clone(), playlist.to_string()));
+    }
+}
+
+fn apply(handle: &MprisPlayer, command: Command) {
+    let Command::Sync(status, playlist) = command;
+    match status {
+        MusicPlayerStatus::NowPlaying(song) => {
+            handle.set_metadata(song_metadata(&song, &playlist));
+            handle.set_playback_status(PlaybackStatus::Playing);
+        }
+        MusicPlayerStatus::Paused(song) => {
+            handle.set_metadata(song_metadata(&song, &playlist));
+            handle.set_playback_status(PlaybackStatus::Paused);
+        }
+        MusicPlayerStatus::Stopped(_) => {
+            handle.set_playback_status(PlaybackStatus::Stopped);
+        }
+    }
+}
+
+fn song_metadata(song: &Song, playlist: &str) -> Metadata {
+    let mut metadata = Metadata::new();
+    metadata.title = Some(song.title.clone());
+    metadata.artist = Some(vec![song.artist.clone()]);
+    metadata.album = Some(playlist.to_string());
+    metadata.length = song.duration.map(|secs| i64::from(secs) * 1_000_000);
+    metadata
+}
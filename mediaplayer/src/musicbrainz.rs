@@ -0,0 +1,100 @@
+//! Baggrundsberigelse af sang-metadata via MusicBrainz.
+//!
+//! Opslag sker på en separat tråd, så TUI'en aldrig blokerer mens der
+//! ventes på svar fra nettet. Hovedtråden sender forespørgsler ind på én
+//! kanal og poller resultater ud af en anden.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::model::Song;
+use crate::MusicError;
+
+const USER_AGENT: &str = concat!("MusicHoard/", env!("CARGO_PKG_VERSION"));
+
+pub struct LookupRequest {
+    pub playlist: String,
+    pub song: Song,
+}
+
+pub struct LookupResult {
+    pub playlist: String,
+    pub title: String,
+    pub enriched: Result<Song, MusicError>,
+}
+
+/// Ejer de to kanaler dæmonen kommunikerer med hovedtråden over.
+pub struct MusicBrainzDaemon {
+    requests: Sender<LookupRequest>,
+    results: Receiver<LookupResult>,
+}
+
+impl MusicBrainzDaemon {
+    pub fn spawn() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<LookupRequest>();
+        let (result_tx, result_rx) = mpsc::channel::<LookupResult>();
+
+        thread::spawn(move || {
+            for request in request_rx {
+                let enriched = lookup(&request.song);
+                let result = LookupResult {
+                    playlist: request.playlist,
+                    title: request.song.title.clone(),
+                    enriched,
+                };
+                if result_tx.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            requests: request_tx,
+            results: result_rx,
+        }
+    }
+
+    /// Sender en berigelsesforespørgsel uden at vente på svar.
+    pub fn request(&self, playlist: String, song: Song) {
+        let _ = self.requests.send(LookupRequest { playlist, song });
+    }
+
+    /// Henter alle svar der er kommet ind siden sidst, uden at blokere.
+    pub fn poll(&self) -> Vec<LookupResult> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn lookup(song: &Song) -> Result<Song, MusicError> {
+    let url = format!(
+        "https://musicbrainz.org/ws/2/recording/?query=recording:{}&fmt=json",
+        urlencoding::encode(&song.title)
+    );
+
+    let response: serde_json::Value = ureq::get(&url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| MusicError::LookupFailed(e.to_string()))?
+        .into_json()
+        .map_err(|e| MusicError::LookupFailed(e.to_string()))?;
+
+    let recording = response["recordings"]
+        .get(0)
+        .ok_or_else(|| MusicError::LookupFailed(format!("ingen match for '{}'", song.title)))?;
+
+    let mut enriched = song.clone();
+    if let Some(artist) = recording["artist-credit"][0]["name"].as_str() {
+        enriched.artist = artist.to_string();
+    }
+    if let Some(release) = recording["releases"][0]["title"].as_str() {
+        enriched.album = release.to_string();
+    }
+    if let Some(date) = recording["releases"][0]["date"].as_str() {
+        enriched.year = date.get(0..4).and_then(|y| y.parse().ok());
+    }
+    if let Some(length_ms) = recording["length"].as_u64() {
+        enriched.duration = Some((length_ms / 1000) as u32);
+    }
+
+    Ok(enriched)
+}
@@ -0,0 +1,213 @@
+//! Afspilningsbackend bygget på `rodio`.
+//!
+//! `rodio`/`cpal`s `Stream` er bevidst `!Send` på alle platforme, så
+//! [`MusicPlayer`] kan ikke flyttes til eller deles på tværs af tråde. Den
+//! lever derfor alene på sin egen baggrundstråd, ejet af [`PlayerHandle`],
+//! som resten af appen (TUI'en og MPRIS) bruger til at sende kommandoer ind
+//! uden selv at skulle kende til den begrænsning.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+use crate::model::Song;
+use crate::MusicError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MusicPlayerStatus {
+    Stopped(Option<Song>),
+    NowPlaying(Song),
+    Paused(Song),
+}
+
+impl std::fmt::Display for MusicPlayerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MusicPlayerStatus::Stopped(Some(song)) => write!(f, "⏹  Stoppet ({})", song.title),
+            MusicPlayerStatus::Stopped(None) => write!(f, "⏹  Stoppet"),
+            MusicPlayerStatus::NowPlaying(song) => write!(f, "▶  Afspiller: {}", song.title),
+            MusicPlayerStatus::Paused(song) => write!(f, "⏸  Pause: {}", song.title),
+        }
+    }
+}
+
+/// Ejer lydenheden og den `Sink`, som faktisk afspiller sangene.
+///
+/// Bruges udelukkende fra baggrundstråden [`PlayerHandle::spawn`] starter —
+/// se modulets doc-kommentar for hvorfor.
+struct MusicPlayer {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sink: Sink,
+    status: MusicPlayerStatus,
+}
+
+impl MusicPlayer {
+    fn new() -> Result<Self, MusicError> {
+        let (stream, handle) = OutputStream::try_default()
+            .map_err(|e| MusicError::DecodeError(e.to_string()))?;
+        let sink =
+            Sink::try_new(&handle).map_err(|e| MusicError::DecodeError(e.to_string()))?;
+        Ok(Self {
+            _stream: stream,
+            handle,
+            sink,
+            status: MusicPlayerStatus::Stopped(None),
+        })
+    }
+
+    pub fn status(&self) -> &MusicPlayerStatus {
+        &self.status
+    }
+
+    pub fn play(&mut self, song: Song) -> Result<(), MusicError> {
+        let source = decode(&song.path)?;
+
+        self.sink.stop();
+        self.sink =
+            Sink::try_new(&self.handle).map_err(|e| MusicError::DecodeError(e.to_string()))?;
+        self.sink.append(source);
+        self.sink.play();
+        self.status = MusicPlayerStatus::NowPlaying(song);
+        Ok(())
+    }
+
+    pub fn pause(&mut self) {
+        if let MusicPlayerStatus::NowPlaying(song) = &self.status {
+            let song = song.clone();
+            self.sink.pause();
+            self.status = MusicPlayerStatus::Paused(song);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let MusicPlayerStatus::Paused(song) = &self.status {
+            let song = song.clone();
+            self.sink.play();
+            self.status = MusicPlayerStatus::NowPlaying(song);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        let last = match &self.status {
+            MusicPlayerStatus::NowPlaying(song) | MusicPlayerStatus::Paused(song) => {
+                Some(song.clone())
+            }
+            MusicPlayerStatus::Stopped(song) => song.clone(),
+        };
+        self.sink.stop();
+        self.status = MusicPlayerStatus::Stopped(last);
+    }
+}
+
+enum Command {
+    Play(Song, Sender<Result<(), MusicError>>),
+    Pause,
+    Resume,
+    Stop,
+    Status(Sender<MusicPlayerStatus>),
+}
+
+/// Håndtag til en [`MusicPlayer`], der lever på sin egen baggrundstråd.
+///
+/// Kan frit klones og deles mellem tråde (TUI'en, MPRIS-tråden) uden at
+/// krænke `cpal::Stream`s `!Send`-begrænsning, fordi selve afspilleren aldrig
+/// forlader den tråd, [`PlayerHandle::spawn`] starter.
+#[derive(Clone)]
+pub struct PlayerHandle {
+    commands: Sender<Command>,
+}
+
+impl PlayerHandle {
+    pub fn spawn() -> Result<Self, MusicError> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (command_tx, command_rx) = mpsc::channel::<Command>();
+
+        thread::spawn(move || {
+            let mut player = match MusicPlayer::new() {
+                Ok(player) => {
+                    if ready_tx.send(Ok(())).is_err() {
+                        return;
+                    }
+                    player
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            for command in command_rx {
+                match command {
+                    Command::Play(song, reply) => {
+                        let _ = reply.send(player.play(song));
+                    }
+                    Command::Pause => player.pause(),
+                    Command::Resume => player.resume(),
+                    Command::Stop => player.stop(),
+                    Command::Status(reply) => {
+                        let _ = reply.send(player.status().clone());
+                    }
+                }
+            }
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| MusicError::IoError("afspilningstråden stoppede under opstart.".to_string()))??;
+
+        Ok(Self { commands: command_tx })
+    }
+
+    pub fn play(&self, song: Song) -> Result<(), MusicError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.send(Command::Play(song, reply_tx))?;
+        reply_rx
+            .recv()
+            .map_err(|_| MusicError::IoError("afspilningstråden svarede ikke.".to_string()))?
+    }
+
+    pub fn pause(&self) {
+        let _ = self.send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.send(Command::Resume);
+    }
+
+    pub fn stop(&self) {
+        let _ = self.send(Command::Stop);
+    }
+
+    pub fn status(&self) -> MusicPlayerStatus {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        if self.send(Command::Status(reply_tx)).is_err() {
+            return MusicPlayerStatus::Stopped(None);
+        }
+        reply_rx
+            .recv()
+            .unwrap_or(MusicPlayerStatus::Stopped(None))
+    }
+
+    fn send(&self, command: Command) -> Result<(), MusicError> {
+        self.commands
+            .send(command)
+            .map_err(|_| MusicError::IoError("afspilningstråden er stoppet.".to_string()))
+    }
+}
+
+fn decode(path: &Path) -> Result<Decoder<BufReader<File>>, MusicError> {
+    let file = File::open(path).map_err(|e| MusicError::DecodeError(e.to_string()))?;
+    Decoder::new(BufReader::new(file)).map_err(|_| {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("ukendt")
+            .to_string();
+        MusicError::UnsupportedFormat(ext)
+    })
+}
@@ -0,0 +1,319 @@
+//! Import/eksport af afspilningslister i eksterne formater, så brugeren kan
+//! dele lister med andre afspillere.
+//!
+//! Hvert format er en lille plugin, der implementerer [`PlaylistFormat`].
+//! [`format_for_path`] vælger pluginet ud fra filendelsen. Formaterne
+//! arbejder på [`Song`] frem for blotte stier, så titel/kunstner/album/
+//! varighed bevares gennem en import/eksport-tur, i det omfang det valgte
+//! format understøtter dem.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::model::Song;
+use crate::MusicError;
+
+pub trait PlaylistFormat {
+    fn parse(&self, reader: &mut dyn Read) -> Result<Vec<Song>, MusicError>;
+    fn write(&self, tracks: &[Song], writer: &mut dyn Write) -> Result<(), MusicError>;
+}
+
+/// Finder det rette format ud fra filendelsen (`.m3u`, `.pls`, `.xspf`).
+pub fn format_for_path(path: &Path) -> Result<Box<dyn PlaylistFormat>, MusicError> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match ext.as_str() {
+        "m3u" | "m3u8" => Ok(Box::new(Extm3u)),
+        "pls" => Ok(Box::new(Pls)),
+        "xspf" => Ok(Box::new(Xspf)),
+        other => Err(MusicError::UnsupportedFormat(other.to_string())),
+    }
+}
+
+fn read_to_string(reader: &mut dyn Read) -> Result<String, MusicError> {
+    let mut buf = String::new();
+    reader
+        .read_to_string(&mut buf)
+        .map_err(|e| MusicError::IoError(e.to_string()))?;
+    Ok(buf)
+}
+
+fn write_all(writer: &mut dyn Write, s: &str) -> Result<(), MusicError> {
+    writer
+        .write_all(s.as_bytes())
+        .map_err(|e| MusicError::IoError(e.to_string()))
+}
+
+/// Escaper `&`, `<` og `>`, så en titel/sti kan interpoleres sikkert i XML.
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Modsvarer [`escape_xml`] ved parsing. `&amp;` afkodes sidst, så
+/// `&amp;lt;` ikke ender som `<`.
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+// ── EXTM3U ───────────────────────────────────────────────────────────────────
+
+struct Extm3u;
+
+impl PlaylistFormat for Extm3u {
+    fn parse(&self, reader: &mut dyn Read) -> Result<Vec<Song>, MusicError> {
+        let content = read_to_string(reader)?;
+        let mut tracks = Vec::new();
+        let mut pending: Option<(Option<u32>, String)> = None;
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(info) = line.strip_prefix("#EXTINF:") {
+                if let Some((duration, title)) = info.split_once(',') {
+                    pending = Some((duration.trim().parse().ok(), title.trim().to_string()));
+                }
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let mut song = Song::from_path(line);
+            if let Some((duration, title)) = pending.take() {
+                song.title = title;
+                song.duration = duration;
+            }
+            tracks.push(song);
+        }
+
+        Ok(tracks)
+    }
+
+    fn write(&self, tracks: &[Song], writer: &mut dyn Write) -> Result<(), MusicError> {
+        let mut out = String::from("#EXTM3U\n");
+        for track in tracks {
+            let duration = track.duration.map(|d| d as i64).unwrap_or(-1);
+            out.push_str(&format!(
+                "#EXTINF:{},{}\n{}\n",
+                duration,
+                track.title,
+                track.path.display()
+            ));
+        }
+        write_all(writer, &out)
+    }
+}
+
+// ── PLS ──────────────────────────────────────────────────────────────────────
+
+struct Pls;
+
+/// Splitter `"<indeks>=<værdi>"` — resten af linjen efter nøglen er fjernet —
+/// fx `"12=sti.mp3"` (fra `"File12=sti.mp3"`) → `(12, "sti.mp3")`.
+fn split_index(rest: &str) -> Option<(u32, String)> {
+    let (index, value) = rest.split_once('=')?;
+    let index: u32 = index.trim().parse().ok()?;
+    Some((index, value.trim().to_string()))
+}
+
+impl PlaylistFormat for Pls {
+    fn parse(&self, reader: &mut dyn Read) -> Result<Vec<Song>, MusicError> {
+        let content = read_to_string(reader)?;
+
+        #[derive(Default)]
+        struct Entry {
+            path: Option<String>,
+            title: Option<String>,
+            length: Option<u32>,
+        }
+
+        let mut entries: BTreeMap<u32, Entry> = BTreeMap::new();
+        for line in content.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("File") {
+                if let Some((n, path)) = split_index(rest) {
+                    entries.entry(n).or_default().path = Some(path);
+                }
+            } else if let Some(rest) = line.strip_prefix("Title") {
+                if let Some((n, title)) = split_index(rest) {
+                    entries.entry(n).or_default().title = Some(title);
+                }
+            } else if let Some(rest) = line.strip_prefix("Length") {
+                if let Some((n, length)) = split_index(rest) {
+                    entries.entry(n).or_default().length = length.parse().ok();
+                }
+            }
+        }
+
+        Ok(entries
+            .into_values()
+            .filter_map(|entry| {
+                let path = entry.path?;
+                let mut song = Song::from_path(&path);
+                if let Some(title) = entry.title {
+                    song.title = title;
+                }
+                song.duration = entry.length;
+                Some(song)
+            })
+            .collect())
+    }
+
+    fn write(&self, tracks: &[Song], writer: &mut dyn Write) -> Result<(), MusicError> {
+        let mut out = String::from("[playlist]\n");
+        for (i, track) in tracks.iter().enumerate() {
+            let n = i + 1;
+            out.push_str(&format!("File{}={}\n", n, track.path.display()));
+            out.push_str(&format!("Title{}={}\n", n, track.title));
+            let length = track.duration.map(|d| d as i64).unwrap_or(-1);
+            out.push_str(&format!("Length{}={}\n", n, length));
+        }
+        out.push_str(&format!("NumberOfEntries={}\n", tracks.len()));
+        out.push_str("Version=2\n");
+        write_all(writer, &out)
+    }
+}
+
+// ── XSPF ─────────────────────────────────────────────────────────────────────
+
+struct Xspf;
+
+/// Henter teksten mellem `<tag>` og `</tag>` i et enkelt `<track>`-blok.
+fn extract_tag(block: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = block.find(&open)? + open.len();
+    let end = block[start..].find(&close)? + start;
+    Some(unescape_xml(&block[start..end]))
+}
+
+impl PlaylistFormat for Xspf {
+    fn parse(&self, reader: &mut dyn Read) -> Result<Vec<Song>, MusicError> {
+        let content = read_to_string(reader)?;
+        let mut tracks = Vec::new();
+        let mut rest = content.as_str();
+
+        while let Some(start) = rest.find("<track>") {
+            rest = &rest[start + "<track>".len()..];
+            let Some(end) = rest.find("</track>") else {
+                break;
+            };
+            let block = &rest[..end];
+            rest = &rest[end + "</track>".len()..];
+
+            let Some(location) = extract_tag(block, "location") else {
+                continue;
+            };
+            let mut song = Song::from_path(&location);
+            if let Some(title) = extract_tag(block, "title") {
+                song.title = title;
+            }
+            if let Some(creator) = extract_tag(block, "creator") {
+                song.artist = creator;
+            }
+            if let Some(album) = extract_tag(block, "album") {
+                song.album = album;
+            }
+            if let Some(duration) = extract_tag(block, "duration").and_then(|d| d.parse::<u32>().ok()) {
+                song.duration = Some(duration / 1000);
+            }
+            tracks.push(song);
+        }
+
+        Ok(tracks)
+    }
+
+    fn write(&self, tracks: &[Song], writer: &mut dyn Write) -> Result<(), MusicError> {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <trackList>\n",
+        );
+        for track in tracks {
+            out.push_str("    <track>");
+            out.push_str(&format!(
+                "<location>{}</location>",
+                escape_xml(&track.path.display().to_string())
+            ));
+            out.push_str(&format!("<title>{}</title>", escape_xml(&track.title)));
+            if !track.artist.is_empty() {
+                out.push_str(&format!("<creator>{}</creator>", escape_xml(&track.artist)));
+            }
+            if !track.album.is_empty() {
+                out.push_str(&format!("<album>{}</album>", escape_xml(&track.album)));
+            }
+            if let Some(duration) = track.duration {
+                out.push_str(&format!("<duration>{}</duration>", duration * 1000));
+            }
+            out.push_str("</track>\n");
+        }
+        out.push_str("  </trackList>\n</playlist>\n");
+        write_all(writer, &out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn song(title: &str, path: &str) -> Song {
+        let mut s = Song::new(title, path);
+        s.artist = "Kunstner".to_string();
+        s.album = "Album".to_string();
+        s.duration = Some(215);
+        s
+    }
+
+    #[test]
+    fn extm3u_round_trips_title_and_duration() {
+        let songs = vec![song("Alpha", "a.mp3")];
+        let mut buf = Vec::new();
+        Extm3u.write(&songs, &mut buf).unwrap();
+
+        let parsed = Extm3u.parse(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(parsed[0].title, "Alpha");
+        assert_eq!(parsed[0].duration, Some(215));
+    }
+
+    #[test]
+    fn pls_round_trips_title_and_duration() {
+        let songs = vec![song("Beta", "b.mp3")];
+        let mut buf = Vec::new();
+        Pls.write(&songs, &mut buf).unwrap();
+
+        let parsed = Pls.parse(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(parsed[0].title, "Beta");
+        assert_eq!(parsed[0].duration, Some(215));
+    }
+
+    #[test]
+    fn xspf_round_trips_all_metadata() {
+        let songs = vec![song("Gamma", "g.mp3")];
+        let mut buf = Vec::new();
+        Xspf.write(&songs, &mut buf).unwrap();
+
+        let parsed = Xspf.parse(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(parsed[0].title, "Gamma");
+        assert_eq!(parsed[0].artist, "Kunstner");
+        assert_eq!(parsed[0].album, "Album");
+        assert_eq!(parsed[0].duration, Some(215));
+    }
+
+    #[test]
+    fn xspf_escapes_special_characters() {
+        let songs = vec![song("Rock & Roll <Live>", "r.mp3")];
+        let mut buf = Vec::new();
+        Xspf.write(&songs, &mut buf).unwrap();
+        let xml = String::from_utf8(buf.clone()).unwrap();
+
+        assert!(!xml.contains("Rock & Roll <Live>"));
+        assert!(xml.contains("Rock &amp; Roll &lt;Live&gt;"));
+
+        let parsed = Xspf.parse(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(parsed[0].title, "Rock & Roll <Live>");
+    }
+}
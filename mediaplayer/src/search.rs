@@ -0,0 +1,65 @@
+//! Fuldtekstsøgning på tværs af alle afspilningslister.
+
+use regex::Regex;
+
+use crate::{MusicError, Playlists};
+
+/// Finder `(playlistnavn, sangtitel)`-par der matcher `query`.
+///
+/// Almindelige forespørgsler matcher versalufølsomt som en delstreng.
+/// Forespørgsler omsluttet af skråstreger, f.eks. `/^a.*z$/`, behandles som
+/// et regulært udtryk i stedet.
+pub fn search(playlists: &Playlists, query: &str) -> Result<Vec<(String, String)>, MusicError> {
+    if let Some(pattern) = query.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+        let re = Regex::new(pattern).map_err(|e| MusicError::InvalidPattern(e.to_string()))?;
+        Ok(matches(playlists, |title| re.is_match(title)))
+    } else {
+        let needle = query.to_lowercase();
+        Ok(matches(playlists, |title| title.to_lowercase().contains(&needle)))
+    }
+}
+
+fn matches(playlists: &Playlists, predicate: impl Fn(&str) -> bool) -> Vec<(String, String)> {
+    let mut hits = Vec::new();
+    for (name, songs) in playlists {
+        for song in songs {
+            if predicate(&song.title) {
+                hits.push((name.clone(), song.title.clone()));
+            }
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Song;
+
+    fn playlists() -> Playlists {
+        let mut playlists = Playlists::new();
+        playlists.insert(
+            "Favoritter".to_string(),
+            vec![Song::new("Alpha", "a.mp3"), Song::new("Beta", "b.mp3")],
+        );
+        playlists
+    }
+
+    #[test]
+    fn substring_search_is_case_insensitive() {
+        let hits = search(&playlists(), "ALPHA").unwrap();
+        assert_eq!(hits, vec![("Favoritter".to_string(), "Alpha".to_string())]);
+    }
+
+    #[test]
+    fn regex_search_matches_pattern() {
+        let hits = search(&playlists(), "/^B.*a$/").unwrap();
+        assert_eq!(hits, vec![("Favoritter".to_string(), "Beta".to_string())]);
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let result = search(&playlists(), "/[/");
+        assert!(matches!(result, Err(MusicError::InvalidPattern(_))));
+    }
+}